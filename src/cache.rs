@@ -0,0 +1,186 @@
+use {
+    crate::{compose::ImportSource, threads::Threads},
+    anyhow::{Context, Result},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Where (if anywhere) to look for and store the already-linked-and-snapshotted component from
+/// a previous [`componentize`](crate::componentize) call, keyed by [`key`].
+#[derive(Clone, Debug)]
+pub enum Cache {
+    Disabled,
+    Dir(PathBuf),
+}
+
+impl Cache {
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        match self {
+            Self::Disabled => None,
+            Self::Dir(dir) => Some(dir.join(key).with_extension("wasm")),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let Some(path) = self.path(key) else {
+            return Ok(None);
+        };
+
+        match fs::read(&path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => {
+                Err(error).with_context(|| format!("unable to read {}", path.display()))
+            }
+        }
+    }
+
+    pub fn put(&self, key: &str, component: &[u8]) -> Result<()> {
+        let Some(path) = self.path(key) else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("unable to create {}", parent.display()))?;
+        }
+
+        fs::write(&path, component)
+            .with_context(|| format!("unable to write {}", path.display()))
+    }
+}
+
+/// Compute a content-addressed key for a [`componentize`](crate::componentize) call from every
+/// input that determines its linked-and-snapshotted output: the WIT world, the Python source
+/// tree(s), the native extensions bundled from them, the app name baked into the snapshot via
+/// `call_init`, whether (and how) wasi-threads is enabled, the interfaces and components composed
+/// in to satisfy otherwise-unresolved imports, and this crate's own version (since its code
+/// generation can change the output independent of any of the above).
+pub fn key(
+    wit_path: &Path,
+    world: Option<&str>,
+    python_path: &[&str],
+    native_extensions: &[PathBuf],
+    app_name: &str,
+    threads: Option<Threads>,
+    import_sources: &[ImportSource],
+) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+
+    hash_path(&mut hasher, wit_path)?;
+    hasher.update(world.unwrap_or("").as_bytes());
+
+    for path in python_path {
+        hash_path(&mut hasher, Path::new(path))?;
+    }
+
+    let mut native_extensions = native_extensions.to_vec();
+    native_extensions.sort();
+    for path in &native_extensions {
+        hash_path(&mut hasher, path)?;
+    }
+
+    hasher.update(app_name.as_bytes());
+
+    match threads {
+        Some(threads) => hasher.update(&[1]).update(&threads.count.to_le_bytes()),
+        None => hasher.update(&[0]),
+    };
+
+    let mut import_sources = import_sources.to_vec();
+    import_sources.sort_by(|a, b| a.interface.cmp(&b.interface));
+    for source in &import_sources {
+        hasher.update(source.interface.as_bytes());
+        hash_path(&mut hasher, &source.component_path)?;
+    }
+
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+fn hash_path(hasher: &mut blake3::Hasher, path: &Path) -> Result<()> {
+    if path.is_dir() {
+        let mut entries = fs::read_dir(path)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            hasher.update(entry.file_name().to_string_lossy().as_bytes());
+            hash_path(hasher, &entry.path())?;
+        }
+    } else if path.is_file() {
+        hasher.update(
+            &fs::read(path).with_context(|| format!("unable to read {}", path.display()))?,
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(app_name: &str, threads: Option<Threads>, import_sources: &[ImportSource]) -> String {
+        super::key(
+            Path::new("/nonexistent/wit"),
+            None,
+            &[],
+            &[],
+            app_name,
+            threads,
+            import_sources,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn differs_by_app_name() {
+        assert_ne!(key("one", None, &[]), key("two", None, &[]));
+    }
+
+    #[test]
+    fn differs_by_thread_count() {
+        assert_ne!(
+            key("app", Some(Threads { count: 1 }), &[]),
+            key("app", Some(Threads { count: 4 }), &[]),
+        );
+    }
+
+    #[test]
+    fn differs_by_threads_enabled() {
+        assert_ne!(
+            key("app", None, &[]),
+            key("app", Some(Threads { count: 1 }), &[]),
+        );
+    }
+
+    #[test]
+    fn differs_by_import_sources() {
+        let with_source = [ImportSource {
+            interface: "wasi:http/outgoing-handler".to_owned(),
+            component_path: PathBuf::from("/nonexistent/handler.wasm"),
+        }];
+
+        assert_ne!(key("app", None, &[]), key("app", None, &with_source));
+    }
+
+    #[test]
+    fn import_source_order_does_not_matter() {
+        let a = ImportSource {
+            interface: "a:a/a".to_owned(),
+            component_path: PathBuf::from("/nonexistent/a.wasm"),
+        };
+        let b = ImportSource {
+            interface: "b:b/b".to_owned(),
+            component_path: PathBuf::from("/nonexistent/b.wasm"),
+        };
+
+        assert_eq!(
+            key("app", None, &[a.clone(), b.clone()]),
+            key("app", None, &[b, a]),
+        );
+    }
+}
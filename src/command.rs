@@ -0,0 +1,246 @@
+use {
+    crate::{Cache, MapDirEntry, Threads, Virtualization},
+    anyhow::{Context, Result},
+    clap::Parser,
+    std::{collections::HashMap, path::PathBuf},
+};
+
+/// Build a component from a WIT world and a Python application, per the `componentize-py
+/// componentize` subcommand.
+#[derive(Parser, Debug)]
+pub struct ComponentizeOpts {
+    /// Path to a WIT file or directory containing the world to target
+    #[clap(long)]
+    pub wit_path: PathBuf,
+
+    /// Name of the world to target, if `wit_path` contains more than one
+    #[clap(long)]
+    pub world: Option<String>,
+
+    /// Directories to search for Python modules, searched in the order given
+    #[clap(long = "python-path", value_name = "DIR")]
+    pub python_path: Vec<String>,
+
+    /// Name under which the interpreter reports the app to Python (e.g. `sys.argv[0]`)
+    #[clap(long)]
+    pub app_name: String,
+
+    /// Where to write the output component
+    #[clap(short, long)]
+    pub output: PathBuf,
+
+    /// Embed a host directory into the output component's virtual filesystem as
+    /// `host_dir::guest_path`; may be given more than once
+    #[clap(long = "map", value_name = "HOST_DIR::GUEST_PATH")]
+    pub map_dirs: Vec<String>,
+
+    /// Bake an environment variable into the output component as `KEY=VALUE`; may be given more
+    /// than once
+    #[clap(long = "env", value_name = "KEY=VALUE")]
+    pub env: Vec<String>,
+
+    /// Leave an interface untouched by virtualization so it still reaches the real host, even
+    /// though `--map`/`--env` are in use (e.g. `wasi:sockets/tcp`); may be given more than once
+    #[clap(long = "allow-host", value_name = "INTERFACE")]
+    pub allow_hosts: Vec<String>,
+
+    /// Enable wasi-threads support, spawning up to N worker threads for Python's `threading`
+    /// and `concurrent.futures`; omit to leave the component single-threaded
+    #[clap(long, value_name = "N")]
+    pub threads: Option<u32>,
+
+    /// Directory in which to cache linked-and-initialized components across builds, keyed by
+    /// their WIT world, Python sources, and other inputs (see `cache::key`)
+    #[clap(long, value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Disable the build cache, even if --cache-dir is set
+    #[clap(long)]
+    pub no_cache: bool,
+}
+
+impl ComponentizeOpts {
+    /// Build the [`Virtualization`] described by `--map`/`--env`/`--allow-host`, or `None` if
+    /// none of those were given.
+    fn virtualization(&self) -> Result<Option<Virtualization>> {
+        if self.map_dirs.is_empty() && self.env.is_empty() && self.allow_hosts.is_empty() {
+            return Ok(None);
+        }
+
+        let map_dirs = self
+            .map_dirs
+            .iter()
+            .map(|entry| parse_map_dir(entry))
+            .collect::<Result<Vec<_>>>()?;
+
+        let env = self
+            .env
+            .iter()
+            .map(|entry| parse_env(entry))
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        Ok(Some(Virtualization {
+            map_dirs,
+            env,
+            allow_hosts: self.allow_hosts.clone(),
+        }))
+    }
+}
+
+fn parse_map_dir(entry: &str) -> Result<MapDirEntry> {
+    let (host_path, guest_path) = entry
+        .split_once("::")
+        .with_context(|| format!("expected HOST_DIR::GUEST_PATH, got {entry:?}"))?;
+
+    Ok(MapDirEntry {
+        host_path: PathBuf::from(host_path),
+        guest_path: guest_path.to_owned(),
+    })
+}
+
+fn parse_env(entry: &str) -> Result<(String, String)> {
+    let (key, value) = entry
+        .split_once('=')
+        .with_context(|| format!("expected KEY=VALUE, got {entry:?}"))?;
+
+    Ok((key.to_owned(), value.to_owned()))
+}
+
+fn threads(opts: &ComponentizeOpts) -> Option<Threads> {
+    opts.threads.map(|count| Threads { count })
+}
+
+fn cache(opts: &ComponentizeOpts) -> Cache {
+    if opts.no_cache {
+        return Cache::Disabled;
+    }
+
+    match &opts.cache_dir {
+        Some(dir) => Cache::Dir(dir.clone()),
+        None => Cache::Disabled,
+    }
+}
+
+pub async fn componentize(opts: &ComponentizeOpts) -> Result<()> {
+    let python_path = opts
+        .python_path
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+
+    let virtualization = opts.virtualization()?;
+    let threads = threads(opts);
+    let cache = cache(opts);
+
+    crate::componentize(
+        &opts.wit_path,
+        opts.world.as_deref(),
+        &python_path,
+        &opts.app_name,
+        &opts.output,
+        virtualization.as_ref(),
+        &[],
+        threads,
+        &cache,
+        None,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_dir_parses_host_and_guest_path() {
+        let entry = parse_map_dir("./assets::/data").unwrap();
+        assert_eq!(entry.host_path, PathBuf::from("./assets"));
+        assert_eq!(entry.guest_path, "/data");
+    }
+
+    #[test]
+    fn map_dir_rejects_missing_separator() {
+        assert!(parse_map_dir("./assets").is_err());
+    }
+
+    #[test]
+    fn env_parses_key_and_value() {
+        let (key, value) = parse_env("GREETING=hello world").unwrap();
+        assert_eq!(key, "GREETING");
+        assert_eq!(value, "hello world");
+    }
+
+    #[test]
+    fn env_rejects_missing_equals() {
+        assert!(parse_env("GREETING").is_err());
+    }
+
+    #[test]
+    fn allow_host_alone_produces_virtualization() {
+        let opts = ComponentizeOpts {
+            allow_hosts: vec!["wasi:sockets/tcp".to_owned()],
+            ..base_opts()
+        };
+        let virtualization = opts.virtualization().unwrap().unwrap();
+        assert_eq!(virtualization.allow_hosts, vec!["wasi:sockets/tcp"]);
+    }
+
+    #[test]
+    fn no_virtualization_flags_produces_none() {
+        assert!(base_opts().virtualization().unwrap().is_none());
+    }
+
+    fn base_opts() -> ComponentizeOpts {
+        ComponentizeOpts {
+            wit_path: PathBuf::from("wit"),
+            world: None,
+            python_path: Vec::new(),
+            app_name: "app".to_owned(),
+            output: PathBuf::from("out.wasm"),
+            map_dirs: Vec::new(),
+            env: Vec::new(),
+            allow_hosts: Vec::new(),
+            threads: None,
+            cache_dir: None,
+            no_cache: false,
+        }
+    }
+
+    #[test]
+    fn threads_defaults_to_disabled() {
+        assert!(threads(&base_opts()).is_none());
+    }
+
+    #[test]
+    fn threads_carries_requested_count() {
+        let opts = ComponentizeOpts {
+            threads: Some(4),
+            ..base_opts()
+        };
+        assert_eq!(threads(&opts).unwrap().count, 4);
+    }
+
+    #[test]
+    fn cache_defaults_to_disabled() {
+        assert!(matches!(cache(&base_opts()), Cache::Disabled));
+    }
+
+    #[test]
+    fn cache_dir_enables_cache() {
+        let opts = ComponentizeOpts {
+            cache_dir: Some(PathBuf::from("/tmp/componentize-py-cache")),
+            ..base_opts()
+        };
+        assert!(matches!(cache(&opts), Cache::Dir(dir) if dir == PathBuf::from("/tmp/componentize-py-cache")));
+    }
+
+    #[test]
+    fn no_cache_overrides_cache_dir() {
+        let opts = ComponentizeOpts {
+            cache_dir: Some(PathBuf::from("/tmp/componentize-py-cache")),
+            no_cache: true,
+            ..base_opts()
+        };
+        assert!(matches!(cache(&opts), Cache::Disabled));
+    }
+}
@@ -0,0 +1,64 @@
+use {
+    anyhow::{Context, Result},
+    std::{collections::HashMap, fs, path::PathBuf},
+};
+
+/// A component file to compose into the output so that it can satisfy one or more of the
+/// world's otherwise-unresolved imports, keyed by the interface name it exports (e.g.
+/// `wasi:http/outgoing-handler` or `my:db/queries`).
+///
+/// The component at `component_path` must export `interface` in full: composition is wired up
+/// per interface, not per function, so if it only exports some of the interface's functions or
+/// resources, the rest are neither linked nor stubbed and instantiation fails outright with a
+/// generic missing-import error rather than falling back to a trapping stub.
+#[derive(Clone, Debug)]
+pub struct ImportSource {
+    pub interface: String,
+    pub component_path: PathBuf,
+}
+
+/// Compose `component` with the components named in `sources`, wiring each source's exports to
+/// the matching imports of `component` (wasm-compose style graph wiring) so the output no longer
+/// needs those imports satisfied at instantiation time.
+///
+/// Returns `component` unchanged if `sources` is empty.
+pub fn compose(component: Vec<u8>, sources: &[ImportSource]) -> Result<Vec<u8>> {
+    if sources.is_empty() {
+        return Ok(component);
+    }
+
+    let dir = tempfile::tempdir()?;
+    let main_path = dir.path().join("main.wasm");
+    fs::write(&main_path, &component)?;
+
+    let mut dependencies = HashMap::new();
+    for source in sources {
+        dependencies.insert(
+            source.interface.clone(),
+            wasm_compose::config::Dependency {
+                path: source.component_path.clone(),
+            },
+        );
+    }
+
+    let config = wasm_compose::config::Config {
+        dir: dir.path().to_owned(),
+        dependencies,
+        ..Default::default()
+    };
+
+    wasm_compose::composer::ComponentComposer::new(&main_path, &config)
+        .compose()
+        .with_context(|| {
+            format!(
+                "failed to compose {} imported component(s) into output component",
+                sources.len()
+            )
+        })
+}
+
+/// The set of world import interface names satisfied by `sources`, so that
+/// [`add_wasi_and_stubs`](crate::add_wasi_and_stubs) can skip installing trapping stubs for them.
+pub fn composed_interfaces(sources: &[ImportSource]) -> std::collections::HashSet<String> {
+    sources.iter().map(|s| s.interface.clone()).collect()
+}
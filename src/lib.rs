@@ -1,7 +1,7 @@
 #![deny(warnings)]
 
 use {
-    anyhow::{anyhow, Context, Result},
+    anyhow::{anyhow, bail, Context, Result},
     async_trait::async_trait,
     bytes::Bytes,
     component_init::Invoker,
@@ -37,13 +37,24 @@ use {
 mod abi;
 mod bindgen;
 mod bindings;
+mod cache;
 pub mod command;
+mod compose;
 #[cfg(feature = "pyo3")]
 mod python;
 mod summary;
 #[cfg(test)]
 mod test;
+mod threads;
 mod util;
+mod virtualize;
+
+pub use {
+    cache::Cache,
+    compose::ImportSource,
+    threads::Threads,
+    virtualize::{MapDirEntry, Virtualization},
+};
 
 static NATIVE_EXTENSION_SUFFIX: &str = ".cpython-311-wasm32-wasi.so";
 
@@ -134,6 +145,11 @@ impl Invoker for MyInvoker {
         func.post_return_async(&mut self.store).await?;
         Ok(result)
     }
+
+    // `component_init::Invoker` is declared in the external `component_init` crate and only
+    // declares the five methods above, so only zero-argument exports returning `s32`, `s64`,
+    // `float32`, `float64`, or `list<u8>` can be called here; string, record, option/result, and
+    // stream/future returns are not supported.
 }
 
 pub fn generate_bindings(
@@ -165,14 +181,81 @@ def call_import(index: int, args: List[Any], result_count: int) -> List[Any]:
 }
 
 #[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
 pub async fn componentize(
     wit_path: &Path,
     world: Option<&str>,
     python_path: &[&str],
     app_name: &str,
     output_path: &Path,
+    virtualize: Option<&Virtualization>,
+    import_sources: &[ImportSource],
+    threads: Option<Threads>,
+    cache: &Cache,
     add_to_linker: Option<&dyn Fn(&mut Linker<Ctx>) -> Result<()>>,
 ) -> Result<()> {
+    if threads.is_some() && !import_sources.is_empty() {
+        bail!(
+            "--threads is not yet supported together with composed import sources: \
+             composition nests the output inside a wrapper component, so there's no single \
+             embedded core module for wasi-threads to instantiate"
+        );
+    }
+
+    let mut native_extensions = Vec::new();
+    for path in python_path {
+        find_native_extensions(Path::new(path), &mut native_extensions)?;
+    }
+
+    let cache_key = cache::key(
+        wit_path,
+        world,
+        python_path,
+        &native_extensions,
+        app_name,
+        threads,
+        import_sources,
+    )?;
+
+    let component = if let Some(component) = cache.get(&cache_key)? {
+        component
+    } else {
+        let component = link_and_initialize(
+            wit_path,
+            world,
+            python_path,
+            app_name,
+            import_sources,
+            threads,
+            add_to_linker,
+        )
+        .await?;
+        cache.put(&cache_key, &component)?;
+        component
+    };
+
+    let component = if let Some(virtualize) = virtualize {
+        virtualize::compose(component, virtualize)?
+    } else {
+        component
+    };
+
+    fs::write(output_path, component)?;
+
+    Ok(())
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+async fn link_and_initialize(
+    wit_path: &Path,
+    world: Option<&str>,
+    python_path: &[&str],
+    app_name: &str,
+    import_sources: &[ImportSource],
+    threads: Option<Threads>,
+    add_to_linker: Option<&dyn Fn(&mut Linker<Ctx>) -> Result<()>>,
+) -> Result<Vec<u8>> {
     let stdlib = tempfile::tempdir()?;
 
     Archive::new(Decoder::new(Cursor::new(include_bytes!(concat!(
@@ -296,6 +379,8 @@ pub async fn componentize(
     }
 
     let component = linker.encode()?;
+    let component = compose::compose(component, import_sources)?;
+    let composed_interfaces = compose::composed_interfaces(import_sources);
 
     let generated_code = tempfile::tempdir()?;
     let world_dir = generated_code
@@ -361,6 +446,9 @@ pub async fn componentize(
     let mut config = Config::new();
     config.wasm_component_model(true);
     config.async_support(true);
+    if threads.is_some() {
+        threads::configure_engine(&mut config);
+    }
 
     let engine = Engine::new(&config)?;
 
@@ -379,7 +467,18 @@ pub async fn componentize(
         async move {
             let component = &Component::new(&engine, instrumented)?;
             if !added_to_linker {
-                add_wasi_and_stubs(&resolve, world, component, &mut linker)?;
+                if let Some(threads) = threads {
+                    threads::add_to_linker(&engine, instrumented, threads, &mut linker)?;
+                }
+
+                add_wasi_and_stubs(
+                    &resolve,
+                    world,
+                    component,
+                    &composed_interfaces,
+                    threads.is_some(),
+                    &mut linker,
+                )?;
             }
 
             let (init, instance) = Init::instantiate_async(&mut store, component, &linker).await?;
@@ -402,9 +501,7 @@ pub async fn componentize(
         )
     })?;
 
-    fs::write(output_path, component)?;
-
-    Ok(())
+    Ok(component)
 }
 
 fn parse_wit(path: &Path, world: Option<&str>) -> Result<(Resolve, WorldId)> {
@@ -423,6 +520,8 @@ fn add_wasi_and_stubs(
     resolve: &Resolve,
     world: WorldId,
     component: &Component,
+    composed_interfaces: &std::collections::HashSet<String>,
+    threads_enabled: bool,
     linker: &mut Linker<Ctx>,
 ) -> Result<()> {
     wasi_command::add_to_linker(linker)?;
@@ -441,6 +540,19 @@ fn add_wasi_and_stubs(
                     WorldKey::Interface(interface) => resolve.id_of(*interface).unwrap(),
                 };
 
+                // Imports satisfied by a composed-in component already have real
+                // implementations wired into `component`, so don't shadow them with stubs.
+                //
+                // This skips the *whole* interface: `ImportSource` maps an interface name to a
+                // component file, not individual functions/resources within it, so a source that
+                // only exports part of an interface isn't supported -- the unexported rest will
+                // fail to instantiate with a generic missing-import error rather than falling
+                // back to a trapping stub. Callers composing in an `ImportSource` must supply a
+                // component that exports the interface in full.
+                if composed_interfaces.contains(&interface_name) {
+                    continue;
+                }
+
                 let interface = &resolve.interfaces[*interface];
                 for function_name in interface.functions.keys() {
                     stubs
@@ -459,6 +571,13 @@ fn add_wasi_and_stubs(
                 }
             }
             WorldItem::Function(function) => {
+                // `thread-spawn` already has a real implementation installed by
+                // `threads::add_to_linker` when `--threads` is enabled; don't shadow it with a
+                // trapping stub.
+                if threads_enabled && function.name == "thread-spawn" {
+                    continue;
+                }
+
                 stubs
                     .entry(None)
                     .or_default()
@@ -0,0 +1,86 @@
+use {
+    crate::Ctx,
+    anyhow::{anyhow, Context, Result},
+    std::sync::Arc,
+    wasmtime::{component::Linker, Config, Engine, Module},
+    wasmparser::{Parser as WasmParser, Payload},
+};
+
+/// Opt-in `wasi-threads` support, enabling CPython's platform threading shim (and therefore
+/// `threading`/`concurrent.futures`) to spawn real OS threads backed by a shared linear memory.
+///
+/// # GIL implications
+///
+/// Spawning an OS thread does not get a Python program around the GIL: only one thread runs
+/// Python bytecode at a time, same as on a native host. What this buys is concurrency for
+/// blocking I/O and for native extensions that release the GIL while they work, not parallel
+/// CPU-bound Python. Native extensions that assume they run on the single main thread (e.g. ones
+/// that aren't reentrant or that cache thread-local state incorrectly) are not safe to load in
+/// `--threads` mode; callers are responsible for vetting the extensions they bundle.
+#[derive(Clone, Copy, Debug)]
+pub struct Threads {
+    /// Maximum number of worker threads the host will spawn for one instance.
+    pub count: u32,
+}
+
+/// Enable the engine-level features (shared memory, atomics, bulk memory) that a module built
+/// with `wasi-threads` support requires.
+pub fn configure_engine(config: &mut Config) {
+    config.wasm_threads(true);
+    config.wasm_bulk_memory(true);
+}
+
+/// Pull the core module `wit-component` embedded in an encoded component back out.
+///
+/// `wasmtime_wasi_threads` instantiates the guest module itself (once per spawned thread), so it
+/// needs the raw core [`Module`], not the [`Component`](wasmtime::component::Component) that
+/// wraps it. This only works when `component` is built from one merged set of libraries with no
+/// nested sub-components -- e.g. `compose::compose` was a no-op -- so that the first
+/// `ModuleSection` encountered by a flat, depth-0 walk is the one belonging to it rather than to
+/// some other composed-in component. [`componentize`](crate::componentize) rejects `--threads`
+/// together with a non-empty `import_sources` up front so this invariant always holds here.
+fn core_module(engine: &Engine, component: &[u8]) -> Result<Module> {
+    for payload in WasmParser::new(0).parse_all(component) {
+        if let Payload::ModuleSection { range, .. } = payload? {
+            return Module::new(engine, &component[range])
+                .context("failed to decode embedded core module");
+        }
+    }
+
+    Err(anyhow!(
+        "no core module found in component; cannot enable wasi-threads"
+    ))
+}
+
+/// Install a real `thread-spawn` implementation into `linker`, in place of the trapping stub
+/// [`add_wasi_and_stubs`](crate::add_wasi_and_stubs) would otherwise install for it.
+///
+/// `thread-spawn` is a plain world-level import (not a `wasi:*` interface), so it's wired up the
+/// same way the rest of this crate's root-level imports are: as a function on
+/// [`Linker::root`]. Each spawn request is handled in the "reactor" style: the host instantiates
+/// the embedded core module again in a fresh worker [`Store`](wasmtime::Store) that shares the
+/// same [`SharedMemory`](wasmtime::SharedMemory) as the main instance, then calls that worker
+/// instance's `wasi_thread_start(tid, arg)` export on a dedicated OS thread. Join/exit are then
+/// coordinated purely through atomics in the shared memory, same as a native wasi-threads host.
+pub fn add_to_linker(
+    engine: &Engine,
+    component: &[u8],
+    threads: Threads,
+    linker: &mut Linker<Ctx>,
+) -> Result<Arc<wasmtime_wasi_threads::WasiThreadsCtx<Ctx>>> {
+    let module = core_module(engine, component)?;
+
+    let wasi_threads = Arc::new(
+        wasmtime_wasi_threads::WasiThreadsCtx::new(module.clone(), Arc::new(engine.clone()))
+            .context("failed to construct wasi-threads context")?,
+    );
+
+    // Hand the real concurrency accounting (how many worker threads are *currently* alive, vs.
+    // how many have ever been spawned) to `wasmtime_wasi_threads` itself, which tracks
+    // completions; a hand-rolled counter here would have no way to learn when a spawned thread
+    // exits and would eventually refuse every further spawn for a long-running instance.
+    wasmtime_wasi_threads::add_to_linker(linker, &wasi_threads, &module, threads.count)
+        .context("failed to add wasi-threads imports to linker")?;
+
+    Ok(wasi_threads)
+}
@@ -0,0 +1,67 @@
+use {
+    anyhow::{Context, Result},
+    std::{collections::HashMap, path::PathBuf},
+};
+
+/// A host directory to embed into the output component's virtual filesystem, along with the
+/// guest path under which its contents should appear.
+#[derive(Clone, Debug)]
+pub struct MapDirEntry {
+    pub host_path: PathBuf,
+    pub guest_path: String,
+}
+
+/// Configuration for the virtual WASI adapter composed onto the output component so that it can
+/// run without any host preopens.
+///
+/// Any directory named in `map_dirs` is embedded read-only, and any variable named in `env` is
+/// baked in, as data sections in the adapter; `wasi:filesystem` and `wasi:cli/environment` calls
+/// are then satisfied entirely from that embedded data rather than being forwarded to the host.
+/// Interfaces named in `allow_hosts` are left untouched so they still reach the real host.
+#[derive(Clone, Debug, Default)]
+pub struct Virtualization {
+    pub map_dirs: Vec<MapDirEntry>,
+    pub env: HashMap<String, String>,
+    pub allow_hosts: Vec<String>,
+}
+
+impl Virtualization {
+    pub fn is_empty(&self) -> bool {
+        self.map_dirs.is_empty() && self.env.is_empty() && self.allow_hosts.is_empty()
+    }
+}
+
+/// Compose `component` with a generated virtual adapter that exports the WASI filesystem,
+/// environment, and clocks interfaces and serves them from `virtualization`'s embedded
+/// directories and env map, following the same intercept-and-serve approach as WASI-Virt.
+///
+/// Returns `component` unchanged if `virtualization` is empty (no mapped directories, no env
+/// variables, and no allowed host interfaces); an `allow_hosts` entry alone still composes in the
+/// adapter.
+pub fn compose(component: Vec<u8>, virtualization: &Virtualization) -> Result<Vec<u8>> {
+    if virtualization.is_empty() {
+        return Ok(component);
+    }
+
+    let mut builder = wasi_virt::WasiVirt::new();
+
+    for entry in &virtualization.map_dirs {
+        builder.fs.preload_dir(&entry.host_path, &entry.guest_path);
+    }
+
+    for (key, value) in &virtualization.env {
+        builder.env.insert(key.clone(), value.clone());
+    }
+
+    for interface in &virtualization.allow_hosts {
+        builder.allow_host(interface);
+    }
+
+    let adapter = builder
+        .finish()
+        .context("failed to build virtual WASI adapter")?;
+
+    wasm_compose::composer::ComponentComposer::new_from_bytes(&component, &adapter.adapter)
+        .compose()
+        .context("failed to compose output component with virtual WASI adapter")
+}